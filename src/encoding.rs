@@ -0,0 +1,75 @@
+//! Pluggable wire encodings for the plugin protocol.
+//!
+//! The host and a plugin negotiate an encoding during `--meta-plugin-info`
+//! (via `PluginInfo.encodings`) and the host then selects it with
+//! `--meta-plugin-exec --encoding <name>`. JSON remains the default so older
+//! plugins and hosts keep working unchanged.
+
+use crate::{PlanResponse, PluginRequest};
+
+/// A wire encoding the protocol can negotiate between host and plugin.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Encoding {
+    #[default]
+    Json,
+    MsgPack,
+}
+
+impl Encoding {
+    /// Parse the value of a `--encoding` flag.
+    pub fn from_flag(value: &str) -> anyhow::Result<Self> {
+        match value {
+            "json" => Ok(Encoding::Json),
+            "msgpack" => Ok(Encoding::MsgPack),
+            other => anyhow::bail!("unknown encoding `{other}`, expected `json` or `msgpack`"),
+        }
+    }
+}
+
+/// The `PluginInfo.encodings` default: plain JSON.
+pub fn default_encodings() -> Vec<Encoding> {
+    vec![Encoding::Json]
+}
+
+/// Encodes/decodes protocol frames for one wire encoding.
+pub trait Encoder {
+    /// Decode a `PluginRequest` from raw bytes read off stdin (or a socket).
+    fn decode_request(&self, bytes: &[u8]) -> anyhow::Result<PluginRequest>;
+    /// Encode a `PlanResponse` to raw bytes to write to stdout (or a socket).
+    fn encode_plan(&self, response: &PlanResponse) -> anyhow::Result<Vec<u8>>;
+}
+
+/// The default JSON encoder, matching the protocol's original wire format.
+pub struct JsonEncoder;
+
+impl Encoder for JsonEncoder {
+    fn decode_request(&self, bytes: &[u8]) -> anyhow::Result<PluginRequest> {
+        Ok(serde_json::from_slice(bytes)?)
+    }
+
+    fn encode_plan(&self, response: &PlanResponse) -> anyhow::Result<Vec<u8>> {
+        Ok(serde_json::to_vec(response)?)
+    }
+}
+
+/// MessagePack encoder, for plugins/hosts that negotiate `Encoding::MsgPack`.
+pub struct MsgPackEncoder;
+
+impl Encoder for MsgPackEncoder {
+    fn decode_request(&self, bytes: &[u8]) -> anyhow::Result<PluginRequest> {
+        Ok(rmp_serde::from_slice(bytes)?)
+    }
+
+    fn encode_plan(&self, response: &PlanResponse) -> anyhow::Result<Vec<u8>> {
+        Ok(rmp_serde::to_vec(response)?)
+    }
+}
+
+/// Look up the `Encoder` implementation for a negotiated `Encoding`.
+pub fn encoder_for(encoding: Encoding) -> Box<dyn Encoder> {
+    match encoding {
+        Encoding::Json => Box::new(JsonEncoder),
+        Encoding::MsgPack => Box::new(MsgPackEncoder),
+    }
+}
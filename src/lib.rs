@@ -12,6 +12,13 @@ use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::io::Read;
 
+pub mod encoding;
+pub mod logging;
+pub mod socket;
+pub mod testing;
+
+use encoding::{encoder_for, Encoding};
+
 // ============================================================================
 // Plugin Discovery Types
 // ============================================================================
@@ -26,6 +33,16 @@ pub struct PluginInfo {
     pub description: Option<String>,
     #[serde(default)]
     pub help: Option<PluginHelp>,
+    /// Optional protocol extensions this plugin supports (e.g. `"streaming"`,
+    /// `"local-socket"`), so a host can opt into richer behavior only when
+    /// both sides agree on it.
+    #[serde(default)]
+    pub capabilities: Vec<String>,
+    /// Wire encodings this plugin can decode/encode, in preference order.
+    /// The host picks one both sides support and passes it via
+    /// `--meta-plugin-exec --encoding <name>`.
+    #[serde(default = "encoding::default_encodings")]
+    pub encodings: Vec<Encoding>,
 }
 
 /// Help information for a plugin's commands.
@@ -96,6 +113,12 @@ pub struct ExecutionPlan {
     /// Whether to run commands in parallel (overrides CLI --parallel if set)
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub parallel: Option<bool>,
+    /// Hints that this plan contains one or more `interactive` commands, so
+    /// the host should not treat `parallel` as an invitation to run them
+    /// concurrently with each other. Interactive commands are never run in
+    /// parallel even if `parallel` is set.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub foreground: Option<bool>,
 }
 
 /// A single command to be executed by the host via loop_lib.
@@ -108,6 +131,17 @@ pub struct PlannedCommand {
     /// Environment variables to set for this command's subprocess
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub env: Option<HashMap<String, String>>,
+    /// Whether this command needs direct terminal access (e.g. `git rebase
+    /// -i`, or `git commit` opening `$EDITOR`). The host must move the child
+    /// into the foreground process group (Unix `tcsetpgrp`) and inherit the
+    /// TTY rather than capturing stdout/stderr, restoring control afterward.
+    /// Interactive commands are never run in parallel even if
+    /// `ExecutionPlan.parallel` is set.
+    #[serde(default)]
+    pub interactive: bool,
+    /// Whether, and where, the host should log this command's outcome.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub log: Option<logging::LogOptions>,
 }
 
 /// Wrapper for the execution plan response (the JSON envelope plugins emit).
@@ -122,38 +156,125 @@ pub struct PlanResponse {
 
 /// The result of a plugin command execution.
 pub enum CommandResult {
-    /// A plan of commands to execute via loop_lib
-    Plan(Vec<PlannedCommand>, Option<bool>),
+    /// A plan of commands to execute via loop_lib: `(commands, parallel, foreground)`.
+    Plan(Vec<PlannedCommand>, Option<bool>, Option<bool>),
     /// A message to display (no commands to execute)
     Message(String),
     /// An error occurred
     Error(String),
     /// Show help text (optionally with an error message prefix)
     ShowHelp(Option<String>),
+    /// A sequence of incremental events for a long-running command. Requires
+    /// the plugin to advertise the `"streaming"` capability in `PluginInfo`;
+    /// see [`StreamEvent`].
+    ///
+    /// Over stdio, `run_plugin` always emits these as JSON NDJSON, one event
+    /// per line, regardless of any encoding negotiated via
+    /// `PluginInfo.encodings` / `--encoding`: a `MsgPack`-encoded frame is
+    /// binary and may itself contain a `\n` byte, so it is not safe to
+    /// delimit with newlines. Over `--meta-plugin-socket`, each event is
+    /// written as one length-prefixed JSON frame, also regardless of encoding.
+    Stream(Box<dyn Iterator<Item = StreamEvent>>),
+}
+
+impl std::fmt::Debug for CommandResult {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CommandResult::Plan(commands, parallel, foreground) => f
+                .debug_tuple("Plan")
+                .field(commands)
+                .field(parallel)
+                .field(foreground)
+                .finish(),
+            CommandResult::Message(msg) => f.debug_tuple("Message").field(msg).finish(),
+            CommandResult::Error(e) => f.debug_tuple("Error").field(e).finish(),
+            CommandResult::ShowHelp(e) => f.debug_tuple("ShowHelp").field(e).finish(),
+            CommandResult::Stream(_) => write!(f, "Stream(..)"),
+        }
+    }
+}
+
+/// The capability name a plugin advertises in `PluginInfo.capabilities` to
+/// indicate it may return `CommandResult::Stream`.
+pub const STREAMING_CAPABILITY: &str = "streaming";
+
+/// One incremental event emitted by a plugin running in streaming mode.
+/// Over stdio, `run_plugin` serializes each event as one NDJSON line to
+/// stdout, flushing after every line, so a host can show live progress
+/// instead of waiting for the whole plan. Each event is an object with the
+/// variant name under `"event"` and its payload, if any, under `"data"`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "event", content = "data")]
+pub enum StreamEvent {
+    /// Incremental progress towards `total` units of work.
+    Progress {
+        current: u64,
+        total: u64,
+        message: String,
+    },
+    /// A line of output a plugin wants forwarded immediately.
+    Line { stream: OutputStream, text: String },
+    /// Commands discovered so far; the host may start executing them before
+    /// the plugin finishes planning the rest.
+    PartialPlan(Vec<PlannedCommand>),
+    /// The plugin has no more events to emit.
+    Done,
+}
+
+/// Which stream a `StreamEvent::Line` should be written to by the host.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub enum OutputStream {
+    Stdout,
+    Stderr,
 }
 
 // ============================================================================
 // Helper Functions
 // ============================================================================
 
-/// Serialize and print an execution plan to stdout.
+/// Serialize and print an execution plan to stdout, using JSON.
 pub fn output_execution_plan(commands: Vec<PlannedCommand>, parallel: Option<bool>) {
+    output_execution_plan_with_encoding(commands, parallel, None, Encoding::Json);
+}
+
+/// Serialize and print an execution plan to stdout, using the given encoding.
+pub fn output_execution_plan_with_encoding(
+    commands: Vec<PlannedCommand>,
+    parallel: Option<bool>,
+    foreground: Option<bool>,
+    encoding: Encoding,
+) {
+    use std::io::Write;
     let response = PlanResponse {
-        plan: ExecutionPlan { commands, parallel },
+        plan: ExecutionPlan {
+            commands,
+            parallel,
+            foreground,
+        },
     };
-    println!("{}", serde_json::to_string(&response).unwrap());
+    let bytes = encoder_for(encoding).encode_plan(&response).unwrap();
+    let stdout = std::io::stdout();
+    let mut out = stdout.lock();
+    let _ = out.write_all(&bytes);
+    if encoding == Encoding::Json {
+        let _ = out.write_all(b"\n");
+    }
 }
 
-/// Read and parse a `PluginRequest` from stdin.
+/// Read and parse a `PluginRequest` from stdin, assuming JSON.
 pub fn read_request_from_stdin() -> anyhow::Result<PluginRequest> {
-    let mut input = String::new();
-    std::io::stdin().read_to_string(&mut input)?;
-    let request: PluginRequest = serde_json::from_str(&input)?;
-    Ok(request)
+    read_request_from_stdin_with_encoding(Encoding::Json)
+}
+
+/// Read and parse a `PluginRequest` from stdin, using the given encoding.
+pub fn read_request_from_stdin_with_encoding(encoding: Encoding) -> anyhow::Result<PluginRequest> {
+    let mut input = Vec::new();
+    std::io::stdin().read_to_end(&mut input)?;
+    encoder_for(encoding).decode_request(&input)
 }
 
 /// Write plugin help text to a writer.
-fn write_plugin_help(info: &PluginInfo, w: &mut dyn std::io::Write) {
+pub(crate) fn write_plugin_help(info: &PluginInfo, w: &mut dyn std::io::Write) {
     if let Some(help) = &info.help {
         let _ = writeln!(w, "{}", help.usage);
         let _ = writeln!(w);
@@ -203,7 +324,26 @@ pub struct PluginDefinition {
     pub execute: fn(PluginRequest) -> CommandResult,
 }
 
-/// Run a plugin's main loop. Handles `--meta-plugin-info` and `--meta-plugin-exec` flags.
+/// Parse an optional `--encoding <json|msgpack>` flag out of the arguments
+/// following `--meta-plugin-exec` (or following the socket name of
+/// `--meta-plugin-socket`). Defaults to `Encoding::Json` if absent.
+fn parse_encoding_flag(args: &[String]) -> anyhow::Result<Encoding> {
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        if arg == "--encoding" {
+            let value = iter
+                .next()
+                .ok_or_else(|| anyhow::anyhow!("--encoding requires a value"))?;
+            return Encoding::from_flag(value);
+        }
+    }
+    Ok(Encoding::Json)
+}
+
+/// Run a plugin's main loop. Handles `--meta-plugin-info`, `--meta-plugin-exec`,
+/// and `--meta-plugin-socket` flags. `--meta-plugin-socket` is accepted
+/// whether or not the plugin lists the `"local-socket"` capability in
+/// `PluginInfo.capabilities`.
 ///
 /// This replaces the boilerplate main() function in each plugin binary.
 /// Plugins only need to define their `PluginInfo` and an execute function.
@@ -221,7 +361,15 @@ pub fn run_plugin(plugin: PluginDefinition) {
             println!("{}", json);
         }
         "--meta-plugin-exec" => {
-            let request = match read_request_from_stdin() {
+            let encoding = match parse_encoding_flag(&args[2..]) {
+                Ok(encoding) => encoding,
+                Err(e) => {
+                    eprintln!("{e}");
+                    std::process::exit(1);
+                }
+            };
+
+            let request = match read_request_from_stdin_with_encoding(encoding) {
                 Ok(req) => req,
                 Err(e) => {
                     eprintln!("Failed to parse plugin request: {e}");
@@ -230,8 +378,8 @@ pub fn run_plugin(plugin: PluginDefinition) {
             };
 
             match (plugin.execute)(request) {
-                CommandResult::Plan(commands, parallel) => {
-                    output_execution_plan(commands, parallel);
+                CommandResult::Plan(commands, parallel, foreground) => {
+                    output_execution_plan_with_encoding(commands, parallel, foreground, encoding);
                 }
                 CommandResult::Message(msg) => {
                     if !msg.is_empty() {
@@ -242,6 +390,18 @@ pub fn run_plugin(plugin: PluginDefinition) {
                     eprintln!("Error: {}", e);
                     std::process::exit(1);
                 }
+                CommandResult::Stream(events) => {
+                    // Always JSON NDJSON here, independent of `encoding`: see
+                    // the doc comment on `CommandResult::Stream`.
+                    use std::io::Write;
+                    let stdout = std::io::stdout();
+                    let mut out = stdout.lock();
+                    for event in events {
+                        let line = serde_json::to_string(&event).unwrap();
+                        let _ = writeln!(out, "{}", line);
+                        let _ = out.flush();
+                    }
+                }
                 CommandResult::ShowHelp(maybe_error) => {
                     if let Some(ref err) = maybe_error {
                         eprintln!("error: {}", err);
@@ -255,6 +415,23 @@ pub fn run_plugin(plugin: PluginDefinition) {
                 }
             }
         }
+        "--meta-plugin-socket" => {
+            let Some(name) = args.get(2) else {
+                eprintln!("--meta-plugin-socket requires a socket path/name");
+                std::process::exit(1);
+            };
+            let encoding = match parse_encoding_flag(&args[3..]) {
+                Ok(encoding) => encoding,
+                Err(e) => {
+                    eprintln!("{e}");
+                    std::process::exit(1);
+                }
+            };
+            if let Err(e) = socket::run_over_socket(&plugin, name, encoding) {
+                eprintln!("Error: {e}");
+                std::process::exit(1);
+            }
+        }
         "--help" | "-h" => {
             print_plugin_help(&plugin.info);
         }
@@ -0,0 +1,73 @@
+//! Structured per-command logging and exit-status reporting.
+//!
+//! The protocol otherwise has no way for the host to report back what
+//! actually happened when a plan ran, or for a plugin to request capture of
+//! it. `LogOptions` on `PlannedCommand` lets a plugin ask for output capture
+//! and/or a log file; `PlanExecutionReport` is the envelope the host fills
+//! in with the outcome of each command once the plan has run.
+
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+
+/// Per-command logging options a plugin can request via `PlannedCommand.log`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct LogOptions {
+    /// Capture the command's stdout/stderr into its `CommandOutcome`.
+    #[serde(default)]
+    pub capture_output: bool,
+    /// Append a log entry for this command's outcome to this file.
+    #[serde(default)]
+    pub log_file: Option<String>,
+}
+
+/// The outcome of running one `PlannedCommand`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CommandOutcome {
+    pub dir: String,
+    pub cmd: String,
+    /// The process exit status, normalized across platforms (the exit code,
+    /// or the negated signal number on Unix if the command was killed by a
+    /// signal).
+    pub exit_status: i32,
+    pub duration_ms: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stdout: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stderr: Option<String>,
+}
+
+/// A report of how an entire `ExecutionPlan` ran, for diagnosing failures
+/// across many projects after the fact.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlanExecutionReport {
+    pub commands: Vec<CommandOutcome>,
+}
+
+/// Append each outcome in `report` to `log_file`, one block per command,
+/// prefixed with `timestamp` (caller-supplied, e.g. RFC 3339). Normalizes the
+/// "exit code"/"exit status" wording across platforms to `exit status`.
+pub fn append_report(
+    log_file: &str,
+    report: &PlanExecutionReport,
+    timestamp: &str,
+) -> std::io::Result<()> {
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(log_file)?;
+
+    for outcome in &report.commands {
+        writeln!(file, "[{timestamp}] {} (dir: {})", outcome.cmd, outcome.dir)?;
+        writeln!(file, "  exit status: {}", outcome.exit_status)?;
+        writeln!(file, "  duration: {}ms", outcome.duration_ms)?;
+        if let Some(stdout) = &outcome.stdout {
+            writeln!(file, "  stdout:\n{stdout}")?;
+        }
+        if let Some(stderr) = &outcome.stderr {
+            writeln!(file, "  stderr:\n{stderr}")?;
+        }
+        writeln!(file)?;
+    }
+
+    Ok(())
+}
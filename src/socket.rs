@@ -0,0 +1,94 @@
+//! Local-socket transport, as an alternative to stdio.
+//!
+//! Some plugins want to draw a TUI or drive an interactive pager, which is
+//! impossible while stdio is reserved for the JSON protocol. A plugin that
+//! advertises the `"local-socket"` capability may instead be invoked with
+//! `--meta-plugin-socket <path-or-name>`: the host creates a local socket
+//! (a Unix domain socket on Unix, a named pipe on Windows, via the
+//! `interprocess` crate) and the plugin connects to it, leaving stdio free
+//! for a terminal UI.
+//!
+//! Frames are length-prefixed (a 4-byte little-endian length followed by
+//! that many encoded bytes) since, unlike stdio request/response, a socket
+//! connection has no natural message boundary.
+
+use std::io::{Read, Write};
+
+use interprocess::local_socket::LocalSocketStream;
+
+use crate::encoding::{encoder_for, Encoding};
+use crate::{CommandResult, PluginDefinition};
+
+/// The capability name a plugin advertises in `PluginInfo.capabilities` to
+/// indicate it supports `--meta-plugin-socket`.
+pub const LOCAL_SOCKET_CAPABILITY: &str = "local-socket";
+
+fn read_frame(stream: &mut LocalSocketStream) -> anyhow::Result<Vec<u8>> {
+    let mut len_buf = [0u8; 4];
+    stream.read_exact(&mut len_buf)?;
+    let len = u32::from_le_bytes(len_buf) as usize;
+    let mut buf = vec![0u8; len];
+    stream.read_exact(&mut buf)?;
+    Ok(buf)
+}
+
+fn write_frame(stream: &mut LocalSocketStream, bytes: &[u8]) -> anyhow::Result<()> {
+    let len = u32::try_from(bytes.len())?;
+    stream.write_all(&len.to_le_bytes())?;
+    stream.write_all(bytes)?;
+    stream.flush()?;
+    Ok(())
+}
+
+/// Connect to the host's local socket at `name`, read one `PluginRequest`
+/// frame decoded with `encoding`, and execute it. A plan is written back as
+/// one frame encoded with `encoding`; a message or help text as one frame of
+/// raw text; a stream as one JSON frame per event. `CommandResult::Error`
+/// writes no frame and is returned as `Err`.
+pub fn run_over_socket(
+    plugin: &PluginDefinition,
+    name: &str,
+    encoding: Encoding,
+) -> anyhow::Result<()> {
+    let mut stream = LocalSocketStream::connect(name)?;
+    let encoder = encoder_for(encoding);
+
+    let request_bytes = read_frame(&mut stream)?;
+    let request = encoder.decode_request(&request_bytes)?;
+
+    match (plugin.execute)(request) {
+        CommandResult::Plan(commands, parallel, foreground) => {
+            let response = crate::PlanResponse {
+                plan: crate::ExecutionPlan {
+                    commands,
+                    parallel,
+                    foreground,
+                },
+            };
+            let bytes = encoder.encode_plan(&response)?;
+            write_frame(&mut stream, &bytes)?;
+        }
+        CommandResult::Message(msg) => {
+            write_frame(&mut stream, msg.as_bytes())?;
+        }
+        CommandResult::Error(e) => {
+            anyhow::bail!(e);
+        }
+        CommandResult::ShowHelp(maybe_error) => {
+            let mut buf = Vec::new();
+            crate::write_plugin_help(&plugin.info, &mut buf);
+            write_frame(&mut stream, &buf)?;
+            if let Some(err) = maybe_error {
+                anyhow::bail!(err);
+            }
+        }
+        CommandResult::Stream(events) => {
+            for event in events {
+                let bytes = serde_json::to_vec(&event)?;
+                write_frame(&mut stream, &bytes)?;
+            }
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,189 @@
+//! In-process test harness for plugin authors.
+//!
+//! `PluginTester` drives a `PluginDefinition` directly, without spawning a
+//! subprocess or round-tripping through stdin/stdout. This lets a plugin
+//! crate write ordinary `#[test]` functions against its `execute` fn instead
+//! of hand-rolling integration tests around the raw JSON protocol.
+
+use crate::{
+    write_plugin_help, CommandResult, PlannedCommand, PluginDefinition, PluginRequest,
+    PluginRequestOptions,
+};
+
+/// Drives a `PluginDefinition`'s `execute` fn in-process for testing.
+pub struct PluginTester {
+    plugin: PluginDefinition,
+}
+
+impl PluginTester {
+    /// Wrap a `PluginDefinition` for in-process testing.
+    pub fn new(plugin: PluginDefinition) -> Self {
+        Self { plugin }
+    }
+
+    /// Start building a `PluginRequest` for the given command.
+    pub fn request(&self, command: impl Into<String>) -> RequestBuilder<'_> {
+        RequestBuilder {
+            tester: self,
+            request: PluginRequest {
+                command: command.into(),
+                args: Vec::new(),
+                projects: Vec::new(),
+                cwd: String::new(),
+                options: PluginRequestOptions::default(),
+            },
+        }
+    }
+
+    /// Render this plugin's help text and verify every command listed in
+    /// `PluginInfo.commands` has a matching entry in `PluginHelp.commands`.
+    ///
+    /// Panics with the missing command names if any are undocumented.
+    pub fn verify_help(&self) {
+        let info = &self.plugin.info;
+        let mut buf = Vec::new();
+        write_plugin_help(info, &mut buf);
+
+        if info.commands.is_empty() {
+            return;
+        }
+
+        let Some(help) = &info.help else {
+            panic!("plugin `{}` has commands but no `help` block", info.name);
+        };
+
+        let missing: Vec<&str> = info
+            .commands
+            .iter()
+            .map(String::as_str)
+            .filter(|cmd| !help.commands.contains_key(*cmd))
+            .collect();
+
+        if !missing.is_empty() {
+            panic!(
+                "plugin `{}` is missing help text for commands: {}",
+                info.name,
+                missing.join(", ")
+            );
+        }
+    }
+
+    /// Parse each string in `PluginHelp.examples` into a `PluginRequest` and
+    /// run it, panicking if any documented example returns `CommandResult::Error`.
+    pub fn verify_examples(&self) {
+        let info = &self.plugin.info;
+        let Some(help) = &info.help else {
+            return;
+        };
+
+        for example in &help.examples {
+            let mut tokens = example.split_whitespace();
+            // Skip leading tokens that just name the binary/plugin (e.g. "meta git").
+            let mut token = tokens.next();
+            if token == Some("meta") {
+                token = tokens.next();
+            }
+            if token == Some(info.name.as_str()) {
+                token = tokens.next();
+            }
+
+            let Some(command) = token else {
+                panic!("example `{example}` has no command to run");
+            };
+
+            let args: Vec<String> = tokens.map(String::from).collect();
+            let result = (self.plugin.execute)(PluginRequest {
+                command: command.to_string(),
+                args,
+                projects: Vec::new(),
+                cwd: String::new(),
+                options: PluginRequestOptions::default(),
+            });
+
+            if let CommandResult::Error(e) = result {
+                panic!("example `{example}` returned an error: {e}");
+            }
+        }
+    }
+}
+
+/// Fluent builder for a `PluginRequest`, returned by `PluginTester::request`.
+pub struct RequestBuilder<'a> {
+    tester: &'a PluginTester,
+    request: PluginRequest,
+}
+
+impl<'a> RequestBuilder<'a> {
+    /// Set the request's args.
+    pub fn args<I, S>(mut self, args: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.request.args = args.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Set the request's projects.
+    pub fn projects<I, S>(mut self, projects: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.request.projects = projects.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Set the request's cwd.
+    pub fn cwd(mut self, cwd: impl Into<String>) -> Self {
+        self.request.cwd = cwd.into();
+        self
+    }
+
+    /// Set the request's options.
+    pub fn options(mut self, options: PluginRequestOptions) -> Self {
+        self.request.options = options;
+        self
+    }
+
+    /// Invoke the plugin's `execute` fn with the built request.
+    pub fn run(self) -> CommandResult {
+        (self.tester.plugin.execute)(self.request)
+    }
+}
+
+/// Assertion helpers for a `CommandResult`, for use in plugin test suites.
+pub trait CommandResultAssertions {
+    /// Assert this result is `CommandResult::Plan` and run `f` against its commands.
+    fn assert_plan<F: FnOnce(&[PlannedCommand])>(&self, f: F) -> &Self;
+    /// Assert this result is `CommandResult::Message` containing `needle`.
+    fn assert_message_contains(&self, needle: &str) -> &Self;
+    /// Assert this result is `CommandResult::Error` containing `needle`.
+    fn assert_error(&self, needle: &str) -> &Self;
+}
+
+impl CommandResultAssertions for CommandResult {
+    fn assert_plan<F: FnOnce(&[PlannedCommand])>(&self, f: F) -> &Self {
+        match self {
+            CommandResult::Plan(commands, _, _) => f(commands),
+            other => panic!("expected CommandResult::Plan, got {other:?}"),
+        }
+        self
+    }
+
+    fn assert_message_contains(&self, needle: &str) -> &Self {
+        match self {
+            CommandResult::Message(msg) if msg.contains(needle) => {}
+            other => panic!("expected CommandResult::Message containing {needle:?}, got {other:?}"),
+        }
+        self
+    }
+
+    fn assert_error(&self, needle: &str) -> &Self {
+        match self {
+            CommandResult::Error(e) if e.contains(needle) => {}
+            other => panic!("expected CommandResult::Error containing {needle:?}, got {other:?}"),
+        }
+        self
+    }
+}